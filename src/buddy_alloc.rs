@@ -0,0 +1,636 @@
+//! The core buddy allocator.
+//!
+//! Memory handed to [`BuddyAlloc::new`] (or later [`BuddyAlloc::add_region`])
+//! is organized as a binary tree of power-of-two blocks ("orders"). Order
+//! `0` blocks are `LEAF_SIZE` bytes, and order `k` blocks are
+//! `block_size(k) == LEAF_SIZE << k` bytes. A small amount of bookkeeping
+//! (per-region `split`/`alloc` bitmaps) is carved out of the front of each
+//! managed region so the allocator needs no backing allocator of its own.
+//! Free lists are shared across regions: a block of order `k` from any
+//! region can satisfy a request for order `k`, regardless of which region
+//! produced it.
+//!
+//! Freeing only needs the pointer, not the original size: [`BuddyAlloc`]
+//! walks a region's `split` bitmap to recover the order a pointer was
+//! allocated at, then coalesces with its buddy whenever the buddy is also
+//! free, toggling a single bit per pair (the `alloc` bitmap) to detect that
+//! in O(1) rather than re-deriving it from both children's state.
+
+use core::ptr::{null_mut, NonNull};
+
+/// Smallest block size handed out by the allocator, in bytes. Also the
+/// granularity used to store each free list's intrusive "next" pointer.
+pub const LEAF_SIZE: usize = 16;
+
+/// Error returned by the `try_*` methods when a request can't be
+/// satisfied: either the heap is exhausted, or the requested size
+/// exceeds the largest block the allocator could ever hand out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+/// Maximum number of orders supported. `block_size(MAX_SIZES - 1)` is far
+/// larger than any real heap, so this only bounds the size of fixed-size
+/// bookkeeping arrays held inline in [`BuddyAlloc`] and [`Region`].
+const MAX_SIZES: usize = 48;
+
+/// Maximum number of discontiguous regions a single allocator can manage;
+/// see [`BuddyAlloc::add_region`].
+const MAX_REGIONS: usize = 8;
+
+/// Largest alignment [`BuddyAlloc::add_region`] guarantees an order-`k`
+/// block can satisfy. Each region's data start is rounded up to this
+/// alignment (see `add_region`), which bounds the rounding waste to a
+/// fixed, heap-size-independent amount instead of scaling with the whole
+/// region like aligning to the top order's own size would. Callers
+/// requesting a larger alignment (e.g. [`Locked`](crate::Locked)'s
+/// `GlobalAlloc` impl) can't be guaranteed it and must fail instead.
+pub(crate) const MAX_ALIGN: usize = 4096;
+
+/// Size in bytes of an order-`k` block.
+#[inline]
+pub fn block_size(k: usize) -> usize {
+    LEAF_SIZE << k
+}
+
+/// Smallest order `k` such that `block_size(k) >= n`.
+pub(crate) fn first_up_k(n: usize) -> usize {
+    let mut k = 0;
+    let mut size = LEAF_SIZE;
+    while size < n {
+        k += 1;
+        size *= 2;
+    }
+    k
+}
+
+fn floor_log2(n: usize) -> usize {
+    (usize::BITS - 1 - n.leading_zeros()) as usize
+}
+
+/// Smallest `k` such that `2usize.pow(k) >= n` (for `n >= 1`).
+fn ceil_log2(n: usize) -> usize {
+    if n <= 1 {
+        0
+    } else {
+        floor_log2(n - 1) + 1
+    }
+}
+
+fn align_up(n: usize, align: usize) -> usize {
+    n.div_ceil(align) * align
+}
+
+fn bytes_for_bits(bits: usize) -> usize {
+    bits.div_ceil(8)
+}
+
+#[inline]
+unsafe fn bit_set(bitmap: *mut u8, i: usize) {
+    let byte = bitmap.add(i / 8);
+    *byte |= 1 << (i % 8);
+}
+
+#[inline]
+unsafe fn bit_clear(bitmap: *mut u8, i: usize) {
+    let byte = bitmap.add(i / 8);
+    *byte &= !(1 << (i % 8));
+}
+
+#[inline]
+unsafe fn bit_isset(bitmap: *mut u8, i: usize) -> bool {
+    (*bitmap.add(i / 8) >> (i % 8)) & 1 == 1
+}
+
+/// Toggles the bit and returns its new value.
+#[inline]
+unsafe fn bit_toggle(bitmap: *mut u8, i: usize) -> bool {
+    let byte = bitmap.add(i / 8);
+    *byte ^= 1 << (i % 8);
+    (*byte >> (i % 8)) & 1 == 1
+}
+
+/// Bookkeeping for one contiguous span of memory registered with the
+/// allocator, either via [`BuddyAlloc::new`] or [`BuddyAlloc::add_region`].
+#[derive(Clone, Copy)]
+struct Region {
+    base: usize,
+    /// Bytes actually covered by leaves (may be less than the span passed
+    /// to `add_region` once rounded down to whole leaves).
+    span: usize,
+    /// Number of orders this region's bitmaps were sized for.
+    nsizes: usize,
+    split_bits: [*mut u8; MAX_SIZES],
+    alloc_bits: [*mut u8; MAX_SIZES],
+}
+
+/// A buddy allocator, optionally managing several discontiguous regions of
+/// memory at once.
+///
+/// Construct with [`BuddyAlloc::new`], then call [`BuddyAlloc::malloc`] and
+/// [`BuddyAlloc::free`] as you would the C functions of the same name.
+pub struct BuddyAlloc {
+    regions: [Region; MAX_REGIONS],
+    nregions: usize,
+    nsizes: usize,
+    free_list: [usize; MAX_SIZES],
+}
+
+impl BuddyAlloc {
+    /// Creates an allocator managing `[lower, upper)`.
+    ///
+    /// # Safety
+    ///
+    /// `[lower, upper)` must be a valid, exclusively-owned region of memory
+    /// that outlives the returned `BuddyAlloc`, and must be large enough to
+    /// hold at least one `LEAF_SIZE` block plus the small amount of
+    /// bookkeeping the allocator carves out of its front.
+    pub unsafe fn new(lower: usize, upper: usize) -> Self {
+        let empty_region = Region {
+            base: 0,
+            span: 0,
+            nsizes: 0,
+            split_bits: [null_mut(); MAX_SIZES],
+            alloc_bits: [null_mut(); MAX_SIZES],
+        };
+        let mut alloc = BuddyAlloc {
+            regions: [empty_region; MAX_REGIONS],
+            nregions: 0,
+            nsizes: 0,
+            free_list: [0; MAX_SIZES],
+        };
+        alloc.add_region(lower, upper);
+        alloc
+    }
+
+    /// Registers the additional span `[lower, upper)` with the allocator,
+    /// carving it into the largest power-of-two, leaf-aligned blocks that
+    /// fit (a greedy binary decomposition, so at most one block is wasted
+    /// to rounding) and pushing each onto its order's free list. Future
+    /// `malloc` calls may be satisfied from this span alongside any
+    /// previously registered ones.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`BuddyAlloc::new`]: `[lower, upper)` must be a
+    /// valid, exclusively-owned region that outlives `self` and doesn't
+    /// overlap any region already registered.
+    pub unsafe fn add_region(&mut self, lower: usize, upper: usize) {
+        assert!(upper > lower, "buddy region must be non-empty");
+        assert!(self.nregions < MAX_REGIONS, "too many buddy regions");
+        let total = upper - lower;
+        let max_leaves = total / LEAF_SIZE;
+        assert!(max_leaves > 0, "buddy region smaller than LEAF_SIZE");
+
+        let prelim_top_k = ceil_log2(max_leaves);
+        let prelim_nsizes = prelim_top_k + 1;
+        let meta_bytes = Self::meta_bytes_for(prelim_nsizes, prelim_top_k);
+
+        // An order-`k` block's address is always `data_start + n *
+        // block_size(k)` for some `n`, so aligning `data_start` itself to
+        // `block_size(k)` is what makes every order-`k` block
+        // `block_size(k)`-aligned, up to the cap this allocator promises.
+        let align_target = block_size(prelim_top_k).min(MAX_ALIGN);
+        let data_start = align_up(lower + meta_bytes, align_target);
+        assert!(data_start < upper, "buddy region too small for bookkeeping");
+        let usable_leaves = (upper - data_start) / LEAF_SIZE;
+        // `top_k` is sized to the smallest power of two covering
+        // `usable_leaves`: the region is addressed as a single conceptual
+        // top-order block, and any padding beyond the real backing memory
+        // is carved off by `seed_block` below and never handed out.
+        let top_k = ceil_log2(usable_leaves);
+        let nsizes = top_k + 1;
+
+        let mut region = Region {
+            base: data_start,
+            span: usable_leaves * LEAF_SIZE,
+            nsizes,
+            split_bits: [null_mut(); MAX_SIZES],
+            alloc_bits: [null_mut(); MAX_SIZES],
+        };
+
+        let mut cursor = lower as *mut u8;
+        for k in 0..nsizes {
+            let nblock_k = 1usize << (top_k - k);
+            let alloc_bytes = bytes_for_bits((nblock_k / 2).max(1));
+            region.alloc_bits[k] = cursor;
+            core::ptr::write_bytes(cursor, 0, alloc_bytes);
+            cursor = cursor.add(alloc_bytes);
+
+            let split_bytes = bytes_for_bits(nblock_k);
+            region.split_bits[k] = cursor;
+            core::ptr::write_bytes(cursor, 0, split_bytes);
+            cursor = cursor.add(split_bytes);
+        }
+        debug_assert!((cursor as usize) <= region.base);
+
+        let region_idx = self.nregions;
+        self.regions[region_idx] = region;
+        self.nregions += 1;
+        self.nsizes = self.nsizes.max(nsizes);
+
+        // Seed free lists by (conceptually) splitting the single top-order
+        // block down to whichever maximal sub-blocks are fully backed by
+        // real memory, setting `split_bits` exactly as a real split would.
+        // Any leaf-aligned leftover beyond `usable_leaves` (from rounding
+        // up to a power of two) is marked permanently allocated instead of
+        // split, so it's never pushed to a free list or coalesced into.
+        self.seed_block(region_idx, 0, top_k, usable_leaves);
+    }
+
+    /// Recursively carves the order-`order` block starting at leaf index
+    /// `leaf_start` (relative to the region's base) down to whichever
+    /// maximal whole blocks fit inside `usable_leaves`, freeing each whole
+    /// block and marking any block that falls entirely outside
+    /// `usable_leaves` as permanently allocated (so it can never be
+    /// coalesced into).
+    fn seed_block(&mut self, region_idx: usize, leaf_start: usize, order: usize, usable_leaves: usize) {
+        let block_leaves = 1usize << order;
+        if leaf_start >= usable_leaves {
+            return;
+        }
+        let base = self.regions[region_idx].base;
+        if leaf_start + block_leaves <= usable_leaves {
+            self.push_free(order, base + leaf_start * LEAF_SIZE);
+            return;
+        }
+        // Straddles the boundary: split in two and recurse into both
+        // halves (one of which may itself straddle further). Splitting
+        // this order-`order` block is the same busy-state transition
+        // `split_down` makes when malloc splits a block, so its
+        // `alloc_bits` pair bit is toggled the same way.
+        let idx = self.blk_index(region_idx, order, base + leaf_start * LEAF_SIZE);
+        unsafe {
+            bit_set(self.regions[region_idx].split_bits[order], idx);
+            bit_toggle(self.regions[region_idx].alloc_bits[order], idx / 2);
+        }
+        let half_leaves = block_leaves / 2;
+        let right_start = leaf_start + half_leaves;
+        if right_start >= usable_leaves {
+            // The right half is entirely padding: mark it permanently
+            // allocated so free() never tries to coalesce across it.
+            let right_idx = self.blk_index(region_idx, order - 1, base + right_start * LEAF_SIZE);
+            unsafe { bit_toggle(self.regions[region_idx].alloc_bits[order - 1], right_idx / 2) };
+        } else {
+            self.seed_block(region_idx, right_start, order - 1, usable_leaves);
+        }
+        self.seed_block(region_idx, leaf_start, order - 1, usable_leaves);
+    }
+
+    fn meta_bytes_for(nsizes: usize, top_k: usize) -> usize {
+        let mut meta_bytes = 0;
+        for k in 0..nsizes {
+            let nblock_k = 1usize << (top_k - k);
+            meta_bytes += bytes_for_bits((nblock_k / 2).max(1));
+            meta_bytes += bytes_for_bits(nblock_k);
+        }
+        meta_bytes
+    }
+
+    /// Finds the region owning `addr`. Panics if `addr` wasn't handed out
+    /// by this allocator, which would mean a caller bug (double free,
+    /// foreign pointer, or similar).
+    fn region_for(&self, addr: usize) -> usize {
+        for i in 0..self.nregions {
+            let r = &self.regions[i];
+            if addr >= r.base && addr < r.base + r.span {
+                return i;
+            }
+        }
+        panic!("pointer does not belong to any region of this BuddyAlloc")
+    }
+
+    #[inline]
+    fn blk_index(&self, region_idx: usize, k: usize, addr: usize) -> usize {
+        (addr - self.regions[region_idx].base) / block_size(k)
+    }
+
+    fn pop_free(&mut self, order: usize) -> usize {
+        let addr = self.free_list[order];
+        debug_assert!(addr != 0);
+        let next = unsafe { (addr as *const usize).read() };
+        self.free_list[order] = next;
+        addr
+    }
+
+    fn push_free(&mut self, order: usize, addr: usize) {
+        unsafe { (addr as *mut usize).write(self.free_list[order]) };
+        self.free_list[order] = addr;
+    }
+
+    fn remove_free(&mut self, order: usize, addr: usize) {
+        if self.free_list[order] == addr {
+            self.free_list[order] = unsafe { (addr as *const usize).read() };
+            return;
+        }
+        let mut cur = self.free_list[order];
+        while cur != 0 {
+            let next = unsafe { (cur as *const usize).read() };
+            if next == addr {
+                let next_next = unsafe { (addr as *const usize).read() };
+                unsafe { (cur as *mut usize).write(next_next) };
+                return;
+            }
+            cur = next;
+        }
+        debug_assert!(false, "buddy block was not on its free list");
+    }
+
+    fn is_in_free_list(&self, order: usize, addr: usize) -> bool {
+        let mut cur = self.free_list[order];
+        while cur != 0 {
+            if cur == addr {
+                return true;
+            }
+            cur = unsafe { (cur as *const usize).read() };
+        }
+        false
+    }
+
+    /// Recovers the order a live allocation was made at, given only its
+    /// pointer: the smallest order whose immediate parent was split.
+    fn find_order(&self, region_idx: usize, addr: usize) -> usize {
+        let nsizes = self.regions[region_idx].nsizes;
+        for k in 0..nsizes {
+            if k + 1 >= nsizes {
+                return k;
+            }
+            let parent_idx = self.blk_index(region_idx, k + 1, addr);
+            if unsafe { bit_isset(self.regions[region_idx].split_bits[k + 1], parent_idx) } {
+                return k;
+            }
+        }
+        nsizes - 1
+    }
+
+    /// Allocates at least `bytes` bytes, returning a null pointer if the
+    /// request cannot be satisfied (too large, or heap exhausted). A thin
+    /// wrapper over [`BuddyAlloc::try_malloc`] for callers that prefer the
+    /// traditional null-sentinel convention.
+    pub fn malloc(&mut self, bytes: usize) -> *mut u8 {
+        self.try_malloc(bytes).map_or(null_mut(), |p| p.as_ptr())
+    }
+
+    /// Allocates at least `bytes` bytes, returning `Err(AllocError)` rather
+    /// than a null pointer if the request cannot be satisfied (too large,
+    /// or heap exhausted).
+    pub fn try_malloc(&mut self, bytes: usize) -> Result<NonNull<u8>, AllocError> {
+        if bytes == 0 {
+            return Err(AllocError);
+        }
+        let k = first_up_k(bytes);
+        if k >= self.nsizes {
+            return Err(AllocError);
+        }
+
+        let mut j = k;
+        while j < self.nsizes && self.free_list[j] == 0 {
+            j += 1;
+        }
+        if j >= self.nsizes {
+            return Err(AllocError);
+        }
+
+        let addr = self.pop_free(j);
+        let region_idx = self.region_for(addr);
+        let addr = self.split_down(region_idx, addr, j, k);
+        let idx = self.blk_index(region_idx, k, addr);
+        unsafe { bit_toggle(self.regions[region_idx].alloc_bits[k], idx / 2) };
+        Ok(unsafe { NonNull::new_unchecked(addr as *mut u8) })
+    }
+
+    /// Splits a free block at order `from` down to order `to`, pushing
+    /// each surplus buddy onto its own free list, and returns the address
+    /// of the order-`to` block that remains (same base address throughout,
+    /// since splitting always keeps the lower half and frees the upper).
+    ///
+    /// Every order the block passes through on the way down stops being a
+    /// whole free block (it's now split), so each one's `alloc_bits` pair
+    /// bit is toggled alongside its `split_bits` bit — `free`'s coalescing
+    /// walk toggles the same bit on the way back up, and relies on this
+    /// having happened for every intermediate order, not just `to`, to
+    /// correctly tell a mergeable buddy from one that's still split or
+    /// allocated further down. Does not touch `alloc_bits[to]`; callers
+    /// toggle the final order's bit themselves once they hand that block
+    /// out.
+    fn split_down(&mut self, region_idx: usize, addr: usize, from: usize, to: usize) -> usize {
+        for order in (to + 1..=from).rev() {
+            let idx = self.blk_index(region_idx, order, addr);
+            unsafe {
+                bit_set(self.regions[region_idx].split_bits[order], idx);
+                bit_toggle(self.regions[region_idx].alloc_bits[order], idx / 2);
+            }
+            let buddy_addr = addr + block_size(order - 1);
+            self.push_free(order - 1, buddy_addr);
+        }
+        addr
+    }
+
+    /// Frees a pointer previously returned by [`BuddyAlloc::malloc`].
+    /// Freeing a null pointer is a no-op.
+    pub fn free(&mut self, ptr: *mut u8) {
+        if ptr.is_null() {
+            return;
+        }
+        let mut cur = ptr as usize;
+        let region_idx = self.region_for(cur);
+        let mut k = self.find_order(region_idx, cur);
+        loop {
+            let region_nsizes = self.regions[region_idx].nsizes;
+            let idx = self.blk_index(region_idx, k, cur);
+            let one_still_allocated =
+                unsafe { bit_toggle(self.regions[region_idx].alloc_bits[k], idx / 2) };
+            if one_still_allocated || k + 1 >= region_nsizes {
+                self.push_free(k, cur);
+                return;
+            }
+
+            let buddy_idx = idx ^ 1;
+            let buddy_addr = self.regions[region_idx].base + buddy_idx * block_size(k);
+            self.remove_free(k, buddy_addr);
+
+            let parent_addr = cur.min(buddy_addr);
+            let parent_idx = self.blk_index(region_idx, k + 1, parent_addr);
+            unsafe { bit_clear(self.regions[region_idx].split_bits[k + 1], parent_idx) };
+
+            cur = parent_addr;
+            k += 1;
+        }
+    }
+
+    /// Resizes a live allocation in place where the buddy structure allows
+    /// it, falling back to malloc+copy+free otherwise.
+    ///
+    /// Shrinking always happens in place: the block is split down to the
+    /// new, smaller order and the surplus buddies are freed. Growing
+    /// happens in place only when the buddies needed to coalesce up to the
+    /// new order are all currently free; otherwise this allocates a new
+    /// block, copies `old_size.min(new_size)` bytes, and frees `ptr`.
+    ///
+    /// `ptr` must have been returned by a prior `malloc`/`realloc` call on
+    /// this allocator with size `old_size`. Passing a null `ptr` behaves
+    /// like `malloc(new_size)`; passing `new_size == 0` behaves like
+    /// `free(ptr)` and returns a null pointer. A thin wrapper over
+    /// [`BuddyAlloc::try_realloc`] for callers that prefer the traditional
+    /// null-sentinel convention.
+    pub fn realloc(&mut self, ptr: *mut u8, old_size: usize, new_size: usize) -> *mut u8 {
+        if ptr.is_null() {
+            return self.malloc(new_size);
+        }
+        if new_size == 0 {
+            self.free(ptr);
+            return null_mut();
+        }
+        let ptr = NonNull::new(ptr).expect("already checked non-null above");
+        self.try_realloc(ptr, old_size, new_size)
+            .map_or(null_mut(), |p| p.as_ptr())
+    }
+
+    /// Resizes the live allocation at `ptr` (sized `old_size`) to
+    /// `new_size`, returning `Err(AllocError)` rather than a null pointer
+    /// if the new size can't be satisfied. See [`BuddyAlloc::realloc`] for
+    /// the in-place growing/shrinking behavior.
+    pub fn try_realloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_size: usize,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = ptr.as_ptr();
+        let old_k = first_up_k(old_size.max(1));
+        let new_k = first_up_k(new_size.max(1));
+        if new_k >= self.nsizes {
+            return Err(AllocError);
+        }
+        if new_k == old_k {
+            return Ok(unsafe { NonNull::new_unchecked(ptr) });
+        }
+
+        let region_idx = self.region_for(ptr as usize);
+        if new_k < old_k {
+            let addr = self.shrink_in_place(region_idx, ptr as usize, old_k, new_k);
+            return Ok(unsafe { NonNull::new_unchecked(addr as *mut u8) });
+        }
+
+        if new_k < self.regions[region_idx].nsizes {
+            if let Some(grown) = self.grow_in_place(region_idx, ptr as usize, old_k, new_k) {
+                return Ok(unsafe { NonNull::new_unchecked(grown as *mut u8) });
+            }
+        }
+
+        let new_ptr = self.try_malloc(new_size)?;
+        unsafe {
+            core::ptr::copy_nonoverlapping(ptr, new_ptr.as_ptr(), old_size.min(new_size));
+        }
+        self.free(ptr);
+        Ok(new_ptr)
+    }
+
+    fn shrink_in_place(&mut self, region_idx: usize, addr: usize, old_k: usize, new_k: usize) -> usize {
+        unsafe {
+            bit_toggle(
+                self.regions[region_idx].alloc_bits[old_k],
+                self.blk_index(region_idx, old_k, addr) / 2,
+            )
+        };
+        let addr = self.split_down(region_idx, addr, old_k, new_k);
+        unsafe {
+            bit_toggle(
+                self.regions[region_idx].alloc_bits[new_k],
+                self.blk_index(region_idx, new_k, addr) / 2,
+            )
+        };
+        addr
+    }
+
+    /// Attempts to grow the block at `addr` from `old_k` to `new_k` by
+    /// repeatedly coalescing with its buddy, without moving it. Returns
+    /// `None` (leaving all bookkeeping untouched) as soon as some buddy in
+    /// the chain isn't a whole free block, or the region can't represent
+    /// order `new_k` at all.
+    ///
+    /// Mirrors `free`'s coalescing walk: every order from `old_k` up to
+    /// (but not including) `new_k` has its `alloc_bits` pair bit toggled to
+    /// undo the busy mark `split_down` left there, same as `free` does on
+    /// its way up. The order-`new_k` pair bit is left alone: that block
+    /// goes from split to allocated, and both count as busy.
+    fn grow_in_place(
+        &mut self,
+        region_idx: usize,
+        addr: usize,
+        old_k: usize,
+        new_k: usize,
+    ) -> Option<usize> {
+        let mut cur = addr;
+        for order in old_k..new_k {
+            let idx = self.blk_index(region_idx, order, cur);
+            let buddy_addr = self.regions[region_idx].base + (idx ^ 1) * block_size(order);
+            if !self.is_in_free_list(order, buddy_addr) {
+                return None;
+            }
+            cur = cur.min(buddy_addr);
+        }
+        if cur != addr {
+            // `addr` is the odd (higher-address) sibling at some level in
+            // the chain, so coalescing would hand back a block based at
+            // `cur`, below `addr` - the caller's data, written at `addr`,
+            // wouldn't be at offset 0 of the returned block. Bail out so
+            // `try_realloc` falls back to malloc+copy+free instead of
+            // silently losing it.
+            return None;
+        }
+
+        let mut cur = addr;
+        for order in old_k..new_k {
+            let idx = self.blk_index(region_idx, order, cur);
+            unsafe { bit_toggle(self.regions[region_idx].alloc_bits[order], idx / 2) };
+            let buddy_addr = self.regions[region_idx].base + (idx ^ 1) * block_size(order);
+            self.remove_free(order, buddy_addr);
+            let parent_addr = cur.min(buddy_addr);
+            let parent_idx = self.blk_index(region_idx, order + 1, parent_addr);
+            unsafe { bit_clear(self.regions[region_idx].split_bits[order + 1], parent_idx) };
+            cur = parent_addr;
+        }
+        Some(cur)
+    }
+
+    /// Allocates at least `bytes` bytes like [`BuddyAlloc::malloc`], also
+    /// returning the full size of the backing block (see
+    /// [`BuddyAlloc::usable_size`]) so callers can grow into the rounding
+    /// slack without a second allocation. Returns `(null_mut(), 0)` if the
+    /// request fails.
+    pub fn malloc_usable(&mut self, bytes: usize) -> (*mut u8, usize) {
+        let ptr = self.malloc(bytes);
+        if ptr.is_null() {
+            (ptr, 0)
+        } else {
+            (ptr, self.usable_size(ptr))
+        }
+    }
+
+    /// Returns the full size of the block backing a live allocation, which
+    /// is always `>=` the size originally requested since the allocator
+    /// rounds every request up to a power-of-two block. A null `ptr`
+    /// reports `0`.
+    pub fn usable_size(&self, ptr: *mut u8) -> usize {
+        if ptr.is_null() {
+            return 0;
+        }
+        let addr = ptr as usize;
+        let region_idx = self.region_for(addr);
+        block_size(self.find_order(region_idx, addr))
+    }
+
+    /// Total bytes currently sitting in free lists, available to satisfy
+    /// future `malloc` calls (subject to fragmentation across orders).
+    pub fn available_bytes(&self) -> usize {
+        let mut total = 0;
+        for k in 0..self.nsizes {
+            let mut cur = self.free_list[k];
+            while cur != 0 {
+                total += block_size(k);
+                cur = unsafe { (cur as *const usize).read() };
+            }
+        }
+        total
+    }
+}
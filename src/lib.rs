@@ -0,0 +1,18 @@
+//! A bare-metal buddy memory allocator intended for `no_std` environments
+//! such as kernels and embedded firmware.
+//!
+//! The allocator splits each managed region of memory into power-of-two
+//! sized blocks (orders) and keeps per-order free lists plus a pair of
+//! bitmaps (`split`/`alloc`) carved out of the front of each region itself,
+//! so no backing allocator is required to run the allocator. More than one
+//! discontiguous region can be registered; see [`BuddyAlloc::add_region`].
+#![cfg_attr(not(test), no_std)]
+
+pub mod buddy_alloc;
+mod non_threadsafe_alloc;
+
+#[cfg(test)]
+mod tests;
+
+pub use buddy_alloc::{block_size, AllocError, BuddyAlloc, LEAF_SIZE};
+pub use non_threadsafe_alloc::Locked;
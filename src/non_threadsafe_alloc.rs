@@ -0,0 +1,97 @@
+//! Adapters that let a [`BuddyAlloc`] back Rust's allocation traits.
+//!
+//! [`BuddyAlloc`] itself takes `&mut self`, which doesn't fit `GlobalAlloc`
+//! (only `&self`) or `allocator_api2::Allocator`. [`Locked`] bridges the gap
+//! with a `no_std`-friendly `UnsafeCell`: it is *not* thread-safe (there is
+//! no locking, only uniqueness-by-fiat), hence the module name. It is meant
+//! for single-threaded embedded targets; wrap it in a real mutex yourself if
+//! more than one execution context can touch the allocator.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::ptr::null_mut;
+
+use crate::buddy_alloc::{block_size, first_up_k, BuddyAlloc, MAX_ALIGN};
+
+/// Wraps a value in an `UnsafeCell` to grant interior mutability through a
+/// shared reference, the way `GlobalAlloc::alloc(&self, ..)` requires.
+///
+/// # Safety
+///
+/// `Locked` performs no synchronization of its own. It is sound to use as
+/// `#[global_allocator]` only on targets where allocation always happens
+/// from a single execution context (e.g. a single-threaded firmware image,
+/// or one protected by disabling interrupts around allocator calls).
+pub struct Locked<A> {
+    inner: UnsafeCell<A>,
+}
+
+unsafe impl<A> Sync for Locked<A> {}
+
+impl<A> Locked<A> {
+    /// Wraps `inner` for use as a `GlobalAlloc`.
+    pub const fn new(inner: A) -> Self {
+        Locked {
+            inner: UnsafeCell::new(inner),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get_mut(&self) -> &mut A {
+        &mut *self.inner.get()
+    }
+}
+
+/// Returns the order to request for `layout`, honoring both its size and
+/// its alignment: since an order-`k` block is `block_size(k)`-aligned
+/// whenever the region's data start is, requesting an order whose block
+/// size is at least `max(layout.size(), layout.align())` guarantees the
+/// returned pointer satisfies the alignment. Returns `None` if
+/// `layout.align()` exceeds [`MAX_ALIGN`], the largest alignment a region
+/// is guaranteed to back.
+fn order_for_layout(layout: Layout) -> Option<usize> {
+    if layout.align() > MAX_ALIGN {
+        return None;
+    }
+    Some(first_up_k(layout.size().max(layout.align()).max(1)))
+}
+
+unsafe impl GlobalAlloc for Locked<BuddyAlloc> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let Some(k) = order_for_layout(layout) else {
+            return null_mut();
+        };
+        self.get_mut().malloc(block_size(k))
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, _layout: Layout) {
+        self.get_mut().free(ptr);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let Some(k) = order_for_layout(layout) else {
+            return null_mut();
+        };
+        let old_size = block_size(k);
+        self.get_mut().realloc(ptr, old_size, new_size)
+    }
+}
+
+#[cfg(feature = "allocator_api2")]
+mod allocator_api2_impl {
+    use super::*;
+    use allocator_api2::alloc::{AllocError, Allocator};
+    use core::ptr::NonNull;
+
+    unsafe impl Allocator for Locked<BuddyAlloc> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let k = order_for_layout(layout).ok_or(AllocError)?;
+            let ptr = unsafe { self.get_mut().try_malloc(block_size(k)) }.map_err(|_| AllocError)?;
+            Ok(NonNull::slice_from_raw_parts(ptr, block_size(k)))
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+            self.get_mut().free(ptr.as_ptr());
+        }
+    }
+}